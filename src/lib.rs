@@ -79,6 +79,9 @@ use core::fmt::Debug;
 use proptest::prelude::RngCore;
 use proptest::test_runner::TestRunner;
 use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 /// The subset of possible [`arbitrary::Arbitrary`] implementations that this
 /// crate works with. The main concern here is the `for<'a> Arbitrary<'a>`
@@ -92,14 +95,49 @@ impl<A: for<'a> arbitrary::Arbitrary<'a> + 'static + Debug + Clone> ArbInterop f
 pub struct ArbStrategy<A: ArbInterop> {
     __ph: PhantomData<A>,
     size: usize,
+    take_rest: bool,
+    growth_factor: usize,
+    max_size: usize,
+}
+
+/// Where [`ArbValueTree::simplify`] is in the reduction ladder it runs over
+/// the entropy buffer. Phases are tried in decreasing aggressiveness and
+/// never revisited once exhausted, so shrinking always makes forward
+/// progress: binary-search the buffer length, then zero out contiguous
+/// spans of geometrically decreasing width, then walk individual nonzero
+/// bytes down toward zero.
+#[derive(Debug)]
+enum ShrinkPhase {
+    /// Bisecting the shortest buffer length that still constructs a value.
+    /// `good` is the shortest length known to work so far (initially the
+    /// full buffer); `bad` is the largest length known *not* to work (or
+    /// `0` if nothing has failed yet).
+    Len { good: usize, bad: usize },
+    /// Zeroing contiguous, non-overlapping spans of `width` bytes, sweeping
+    /// the start position across the buffer a whole span at a time (so the
+    /// sweep at each width costs O(buffer_len / width), not O(buffer_len)).
+    /// `Unstructured` reading zero bytes tends to pick the first enum
+    /// variant and the shortest collection length, so this drives values
+    /// toward their "smallest" shape.
+    ZeroSpans { width: usize, pos: usize },
+    /// Walking the buffer left to right, shrinking each nonzero byte toward
+    /// zero by geometrically decreasing steps (mirroring `ZeroSpans`'s
+    /// halving) rather than one at a time, so a buffer full of large bytes
+    /// doesn't cost up to 255 regenerations per byte.
+    DecrementBytes { idx: usize, step: u8 },
+    /// Nothing left to try.
+    Done,
 }
 
 #[derive(Debug)]
 pub struct ArbValueTree<A: Debug> {
     bytes: Vec<u8>,
     curr: A,
-    prev: Option<A>,
-    next: usize,
+    /// The buffer and value from immediately before the most recently
+    /// accepted simplification, so `complicate` can restore them exactly.
+    prev: Option<(Vec<u8>, A)>,
+    take_rest: bool,
+    phase: ShrinkPhase,
 }
 
 impl<A: ArbInterop> proptest::strategy::ValueTree for ArbValueTree<A> {
@@ -112,10 +150,11 @@ impl<A: ArbInterop> proptest::strategy::ValueTree for ArbValueTree<A> {
     fn complicate(&mut self) -> bool {
         // We can only complicate if we previously simplified. Complicating
         // twice in a row without interleaved simplification is guaranteed to
-        // always yield false for the second call.
-        if let Some(prev) = self.prev.take() {
-            // Throw away the current value!
-            self.curr = prev;
+        // always yield false for the second call: it only ever undoes the
+        // single most recently accepted simplify.
+        if let Some((bytes, curr)) = self.prev.take() {
+            self.bytes = bytes;
+            self.curr = curr;
             true
         } else {
             false
@@ -123,44 +162,189 @@ impl<A: ArbInterop> proptest::strategy::ValueTree for ArbValueTree<A> {
     }
 
     fn simplify(&mut self) -> bool {
-        if self.next == 0 {
-            return false;
-        }
-        self.next -= 1;
-        if let Ok(simpler) = Self::gen_one_with_size(&self.bytes, self.next) {
-            // Throw away the previous value and set the current value as prev.
-            // Advance the iterator and set the current value to the next one.
-            self.prev = Some(core::mem::replace(&mut self.curr, simpler));
-            true
-        } else {
-            false
+        loop {
+            match self.phase {
+                ShrinkPhase::Len { good, bad } => {
+                    if good.saturating_sub(bad) <= 1 {
+                        // Jumping straight to half the (now-minimal) buffer
+                        // length bounds the number of widths tried to
+                        // O(log buffer_len), however large the buffer grew.
+                        self.phase = ShrinkPhase::ZeroSpans {
+                            width: good / 2,
+                            pos: 0,
+                        };
+                        continue;
+                    }
+                    let mid = bad + (good - bad) / 2;
+                    let candidate = self.bytes[..mid].to_vec();
+                    if self.accept_if_constructs(candidate) {
+                        self.phase = ShrinkPhase::Len { good: mid, bad };
+                        return true;
+                    } else {
+                        self.phase = ShrinkPhase::Len { good, bad: mid };
+                    }
+                }
+                ShrinkPhase::ZeroSpans { width, pos } => {
+                    if width == 0 {
+                        self.phase = ShrinkPhase::DecrementBytes {
+                            idx: 0,
+                            step: u8::MAX,
+                        };
+                        continue;
+                    }
+                    if pos + width > self.bytes.len() {
+                        self.phase = ShrinkPhase::ZeroSpans {
+                            width: width / 2,
+                            pos: 0,
+                        };
+                        continue;
+                    }
+                    if self.bytes[pos..pos + width].iter().all(|b| *b == 0) {
+                        // Already all zero: zeroing it again wouldn't change
+                        // anything, so there's nothing to test here.
+                        self.phase = ShrinkPhase::ZeroSpans {
+                            width,
+                            pos: pos + width,
+                        };
+                        continue;
+                    }
+                    let mut candidate = self.bytes.clone();
+                    candidate[pos..pos + width].fill(0);
+                    // Whether or not this span could be zeroed, move on to
+                    // the next (non-overlapping) span rather than sliding
+                    // forward one byte at a time.
+                    let accepted = self.accept_if_constructs(candidate);
+                    self.phase = ShrinkPhase::ZeroSpans {
+                        width,
+                        pos: pos + width,
+                    };
+                    if accepted {
+                        return true;
+                    }
+                }
+                ShrinkPhase::DecrementBytes { idx, step } => {
+                    if idx >= self.bytes.len() {
+                        self.phase = ShrinkPhase::Done;
+                        return false;
+                    }
+                    let byte = self.bytes[idx];
+                    if byte == 0 {
+                        self.phase = ShrinkPhase::DecrementBytes {
+                            idx: idx + 1,
+                            step: u8::MAX,
+                        };
+                        continue;
+                    }
+                    // Bound the step to what the byte can actually give up,
+                    // so e.g. a byte of 3 with step 255 still tries 0 first.
+                    let step = step.min(byte);
+                    let mut candidate = self.bytes.clone();
+                    candidate[idx] = byte - step;
+                    if self.accept_if_constructs(candidate) {
+                        // This byte still has more to give up: keep halving
+                        // the step (instead of always retrying step 1) so a
+                        // byte of 255 costs ~8 regenerations, not 255.
+                        let next_step = ((byte - step) / 2).max(1);
+                        self.phase = ShrinkPhase::DecrementBytes {
+                            idx,
+                            step: next_step,
+                        };
+                        return true;
+                    } else if step > 1 {
+                        self.phase = ShrinkPhase::DecrementBytes {
+                            idx,
+                            step: step / 2,
+                        };
+                    } else {
+                        self.phase = ShrinkPhase::DecrementBytes {
+                            idx: idx + 1,
+                            step: u8::MAX,
+                        };
+                    }
+                }
+                ShrinkPhase::Done => return false,
+            }
         }
     }
 }
 
 impl<A: ArbInterop> ArbStrategy<A> {
     pub fn new(size: usize) -> Self {
+        Self::new_with_mode(size, false)
+    }
+
+    /// Like [`new`](Self::new), but generated values are constructed with
+    /// [`Arbitrary::arbitrary_take_rest`](arbitrary::Arbitrary::arbitrary_take_rest)
+    /// instead of [`Arbitrary::arbitrary`](arbitrary::Arbitrary::arbitrary).
+    pub fn new_take_rest(size: usize) -> Self {
+        Self::new_with_mode(size, true)
+    }
+
+    fn new_with_mode(size: usize, take_rest: bool) -> Self {
         Self {
             __ph: PhantomData,
             size,
+            take_rest,
+            growth_factor: DEFAULT_GROWTH_FACTOR,
+            max_size: size.max(DEFAULT_MAX_SIZE),
         }
     }
+
+    /// Sets the buffer length [`new_tree`](proptest::strategy::Strategy::new_tree)
+    /// gives up growing past, once it stops helping (defaults to
+    /// [`DEFAULT_MAX_SIZE`]).
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size.max(self.size);
+        self
+    }
+
+    /// Sets the multiplier applied to the buffer length each time
+    /// [`new_tree`](proptest::strategy::Strategy::new_tree) runs out of
+    /// entropy (defaults to [`DEFAULT_GROWTH_FACTOR`]).
+    pub fn with_growth_factor(mut self, growth_factor: usize) -> Self {
+        self.growth_factor = growth_factor.max(2);
+        self
+    }
 }
 
 impl<A: ArbInterop> ArbValueTree<A> {
-    fn gen_one_with_size(bytes: &[u8], size: usize) -> Result<A, arbitrary::Error> {
-        let mut unstructured = arbitrary::Unstructured::new(&bytes[0..size]);
-        A::arbitrary(&mut unstructured)
+    fn gen_one(bytes: &[u8], take_rest: bool) -> Result<A, arbitrary::Error> {
+        let mut unstructured = arbitrary::Unstructured::new(bytes);
+        if take_rest {
+            A::arbitrary_take_rest(unstructured)
+        } else {
+            A::arbitrary(&mut unstructured)
+        }
+    }
+
+    /// Tries to construct a value from `candidate`. On success, records the
+    /// current buffer/value as `prev` (so [`complicate`](Self::complicate)
+    /// can restore them) and makes `candidate` the new current buffer/value.
+    fn accept_if_constructs(&mut self, candidate: Vec<u8>) -> bool {
+        match Self::gen_one(&candidate, self.take_rest) {
+            Ok(value) => {
+                let old_bytes = core::mem::replace(&mut self.bytes, candidate);
+                let old_curr = core::mem::replace(&mut self.curr, value);
+                self.prev = Some((old_bytes, old_curr));
+                true
+            }
+            Err(_) => false,
+        }
     }
 
     pub fn new(bytes: Vec<u8>) -> Result<Self, arbitrary::Error> {
-        let next = bytes.len();
-        let curr = Self::gen_one_with_size(&bytes, next)?;
+        Self::new_with_mode(bytes, false)
+    }
+
+    fn new_with_mode(bytes: Vec<u8>, take_rest: bool) -> Result<Self, arbitrary::Error> {
+        let curr = Self::gen_one(&bytes, take_rest)?;
+        let good = bytes.len();
         Ok(Self {
             bytes,
             prev: None,
             curr,
-            next,
+            take_rest,
+            phase: ShrinkPhase::Len { good, bad: 0 },
         })
     }
 }
@@ -170,10 +354,21 @@ impl<A: ArbInterop> proptest::strategy::Strategy for ArbStrategy<A> {
     type Value = A;
 
     fn new_tree(&self, runner: &mut TestRunner) -> proptest::strategy::NewTree<Self> {
+        // Per `arbitrary`'s own docs, `NotEnoughData` is returned only by
+        // `Unstructured::bytes`-style APIs -- ordinary derived impls (the
+        // "nested collections, recursive enums" case this growth policy is
+        // for) silently zero-pad via `Unstructured` instead of erroring. So
+        // `A::size_hint` is the primary signal that `self.size` is too
+        // small, not an `Err`: start from whichever is larger. The
+        // `NotEnoughData` handling below is a fallback for impls that do
+        // report it (and for older `arbitrary` versions).
+        let (lower, upper) = A::size_hint(0);
+        let hinted = upper.unwrap_or(lower);
+        let mut size = self.size.max(hinted).min(self.max_size);
         loop {
-            let mut bytes = std::iter::repeat(0u8).take(self.size).collect::<Vec<u8>>();
+            let mut bytes = std::iter::repeat(0u8).take(size).collect::<Vec<u8>>();
             runner.rng().fill_bytes(&mut bytes);
-            match ArbValueTree::new(bytes) {
+            match ArbValueTree::new_with_mode(bytes, self.take_rest) {
                 Ok(v) => {
                     return Ok(v);
                 }
@@ -182,6 +377,16 @@ impl<A: ArbInterop> proptest::strategy::Strategy for ArbStrategy<A> {
                     // from the given bytes. Try again.
                     runner.reject_local(format!("{e}"))?;
                 }
+                Err(arbitrary::Error::NotEnoughData) if size < self.max_size => {
+                    // `A` read past the end of the buffer -- rather than let
+                    // `Unstructured` zero-pad the tail (biasing towards
+                    // degenerate values) or give up, try again with more
+                    // entropy to work with.
+                    size = size
+                        .saturating_mul(self.growth_factor)
+                        .max(size + 1)
+                        .min(self.max_size);
+                }
                 Err(e) => {
                     return Err(format!("{e}").into());
                 }
@@ -198,11 +403,155 @@ pub fn arb_sized<A: ArbInterop>(size: usize) -> ArbStrategy<A> {
 }
 
 /// Default size (256) passed to [`arb_sized`](crate::arb_sized) by
-/// [`arb`](crate::arb).
+/// [`arb`](crate::arb) when [`A::size_hint`](arbitrary::Arbitrary::size_hint)
+/// doesn't give us anything better to go on.
 pub const DEFAULT_SIZE: usize = 256;
 
-/// Calls [`arb_sized`](crate::arb_sized) with
-/// [`DEFAULT_SIZE`](crate::DEFAULT_SIZE) which is `256`.
+/// Default multiplier [`ArbStrategy::new_tree`](proptest::strategy::Strategy::new_tree)
+/// applies to the buffer length each time it runs out of entropy. See
+/// [`ArbStrategy::with_growth_factor`].
+pub const DEFAULT_GROWTH_FACTOR: usize = 2;
+
+/// Default ceiling on how large [`ArbStrategy::new_tree`](proptest::strategy::Strategy::new_tree)
+/// will grow the buffer before giving up (1 MiB). See
+/// [`ArbStrategy::with_max_size`].
+pub const DEFAULT_MAX_SIZE: usize = 1 << 20;
+
+/// Upper bound on the buffer length [`arb_auto`](crate::arb_auto) will pick
+/// from a [`size_hint`](arbitrary::Arbitrary::size_hint), whether the pick
+/// comes from its upper bound or from a multiple of its lower bound.
+pub const AUTO_SIZE_CEILING: usize = 4096;
+
+/// Multiple of the [`size_hint`](arbitrary::Arbitrary::size_hint) lower
+/// bound used by [`arb_auto`](crate::arb_auto) when no upper bound is
+/// available, to leave room for recursive/collection structure the lower
+/// bound alone doesn't account for.
+const AUTO_SIZE_LOWER_BOUND_MULTIPLE: usize = 8;
+
+/// Picks a buffer size for `A` from [`A::size_hint(0)`](arbitrary::Arbitrary::size_hint):
+/// the upper bound when `arbitrary` provides one, otherwise
+/// [`AUTO_SIZE_LOWER_BOUND_MULTIPLE`] times the lower bound (falling back to
+/// [`DEFAULT_SIZE`] only when `arbitrary` can't give us any bound at all),
+/// clamped to [`AUTO_SIZE_CEILING`]. Tiny types like a 3-byte `Rgb` get a
+/// buffer sized to match -- no `DEFAULT_SIZE` floor -- so entropy isn't
+/// wasted on them. Exposed mainly so callers (and tests) can see what
+/// [`arb_auto`](crate::arb_auto)/[`arb`](crate::arb) would pick for `A`
+/// without having to construct and inspect a strategy.
+pub fn auto_size<A: ArbInterop>() -> usize {
+    let (lower, upper) = A::size_hint(0);
+    let guess = upper.unwrap_or_else(|| {
+        if lower == 0 {
+            DEFAULT_SIZE
+        } else {
+            lower.saturating_mul(AUTO_SIZE_LOWER_BOUND_MULTIPLE)
+        }
+    });
+    guess.min(AUTO_SIZE_CEILING)
+}
+
+/// Like [`arb`](crate::arb), but instead of always allocating
+/// [`DEFAULT_SIZE`](crate::DEFAULT_SIZE) bytes, sizes the entropy buffer
+/// from [`A::size_hint`](arbitrary::Arbitrary::size_hint). This avoids
+/// wasting entropy on tiny types and gives data-hungry types (nested
+/// collections, recursive enums) enough bytes to avoid spurious
+/// `IncorrectFormat` rejections and under-generation. See [`auto_size`].
+pub fn arb_auto<A: ArbInterop>() -> ArbStrategy<A> {
+    arb_sized(auto_size::<A>())
+}
+
+/// Calls [`arb_auto`](crate::arb_auto), which sizes the entropy buffer from
+/// `A`'s [`size_hint`](arbitrary::Arbitrary::size_hint) instead of always
+/// using [`DEFAULT_SIZE`](crate::DEFAULT_SIZE).
 pub fn arb<A: ArbInterop>() -> ArbStrategy<A> {
-    arb_sized(DEFAULT_SIZE)
+    arb_auto()
+}
+
+/// Like [`arb_sized`](crate::arb_sized), but constructs values with
+/// [`Arbitrary::arbitrary_take_rest`](arbitrary::Arbitrary::arbitrary_take_rest)
+/// rather than [`Arbitrary::arbitrary`](arbitrary::Arbitrary::arbitrary). This
+/// is the constructor `cargo-fuzz`-style harnesses actually use: it lets the
+/// final collection/`Vec`/`String` in `A` consume all remaining bytes instead
+/// of reading a length prefix, which tends to produce better-distributed
+/// sizes. Shrinking regenerates values the same way, so the shrunk values
+/// stay faithful to how the value was first constructed.
+pub fn arb_sized_take_rest<A: ArbInterop>(size: usize) -> ArbStrategy<A> {
+    ArbStrategy::new_take_rest(size)
+}
+
+/// Calls [`arb_sized_take_rest`](crate::arb_sized_take_rest) with a buffer
+/// size picked the same way [`arb_auto`](crate::arb_auto) picks one, from
+/// `A`'s [`size_hint`](arbitrary::Arbitrary::size_hint).
+pub fn arb_take_rest<A: ArbInterop>() -> ArbStrategy<A> {
+    arb_sized_take_rest(auto_size::<A>())
+}
+
+/// A [`proptest::strategy::Strategy`] that replays a fixed set of entropy
+/// buffers -- e.g. a `cargo-fuzz`/AFL corpus -- instead of drawing random
+/// bytes from the [`TestRunner`]'s rng. See [`arb_from_corpus`] and
+/// [`arb_from_bytes`].
+#[derive(Debug)]
+pub struct ArbCorpusStrategy<A: ArbInterop> {
+    __ph: PhantomData<A>,
+    bufs: Arc<Vec<Vec<u8>>>,
+    cursor: AtomicUsize,
+    take_rest: bool,
+}
+
+impl<A: ArbInterop> Clone for ArbCorpusStrategy<A> {
+    fn clone(&self) -> Self {
+        // Each clone replays the corpus from the start independently: the
+        // cursor is per-strategy iteration state, not something clones
+        // should share (sharing it would mean two clones racing over the
+        // same sequence instead of each seeing every entry).
+        Self {
+            __ph: PhantomData,
+            bufs: Arc::clone(&self.bufs),
+            cursor: AtomicUsize::new(0),
+            take_rest: self.take_rest,
+        }
+    }
+}
+
+impl<A: ArbInterop> proptest::strategy::Strategy for ArbCorpusStrategy<A> {
+    type Tree = ArbValueTree<A>;
+    type Value = A;
+
+    fn new_tree(&self, _runner: &mut TestRunner) -> proptest::strategy::NewTree<Self> {
+        if self.bufs.is_empty() {
+            return Err("ArbCorpusStrategy: corpus is empty".into());
+        }
+        // Cycle through the corpus in order rather than drawing from the
+        // rng, so running with `cases = bufs.len()` visits every entry once.
+        let idx = self.cursor.fetch_add(1, Ordering::Relaxed) % self.bufs.len();
+        ArbValueTree::new_with_mode(self.bufs[idx].clone(), self.take_rest)
+            .map_err(|e| format!("{e}").into())
+    }
+}
+
+/// Constructs a [`Strategy`](proptest::strategy::Strategy) whose [`new_tree`](proptest::strategy::Strategy::new_tree)
+/// yields [`ArbValueTree`]s built directly from `bufs`, cycling through them
+/// in order, instead of from the [`TestRunner`]'s rng. Each buffer still goes
+/// through [`ArbValueTree::new`], so the full simplify/complicate shrinking
+/// machinery applies -- this lets you load a crash-triggering fuzzer input
+/// and let proptest minimize it.
+pub fn arb_from_bytes<A: ArbInterop>(bufs: Vec<Vec<u8>>) -> ArbCorpusStrategy<A> {
+    ArbCorpusStrategy {
+        __ph: PhantomData,
+        bufs: Arc::new(bufs),
+        cursor: AtomicUsize::new(0),
+        take_rest: false,
+    }
+}
+
+/// Like [`arb_from_bytes`], but reads every regular file in `path` as one
+/// entropy buffer, in the style of a `cargo-fuzz`/AFL corpus directory.
+pub fn arb_from_corpus<A: ArbInterop>(path: impl AsRef<Path>) -> std::io::Result<ArbCorpusStrategy<A>> {
+    let mut bufs = Vec::new();
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            bufs.push(std::fs::read(entry.path())?);
+        }
+    }
+    Ok(arb_from_bytes(bufs))
 }
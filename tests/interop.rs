@@ -16,6 +16,12 @@ impl<'a> Arbitrary<'a> for Rgb {
         let b = u8::arbitrary(u)?;
         Ok(Rgb { r, g, b })
     }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        // Three `u8`s, always: without this override `Arbitrary`'s default
+        // `(0, None)` hides the real cost from `auto_size`.
+        (3, Some(3))
+    }
 }
 
 // Part 2: suppose you later decide that in addition to fuzzing
@@ -31,3 +37,135 @@ proptest! {
         prop_assert!(color.g == 0 || color.r > color.g);
     }
 }
+
+// `arb`/`arb_auto` size the entropy buffer from `A::size_hint` instead of
+// always allocating `DEFAULT_SIZE` bytes.
+#[test]
+fn arb_auto_sizes_the_buffer_from_size_hint() {
+    use proptest_arbitrary_interop::{auto_size, DEFAULT_SIZE};
+
+    // `Rgb` has a fixed, 3-byte size_hint, so it shouldn't get the old flat
+    // 256-byte default -- that would be almost all wasted entropy.
+    assert_eq!(auto_size::<Rgb>(), 3);
+
+    // A type with a hefty fixed size (`arbitrary` reports 512 bytes as both
+    // its lower and upper bound) should get more than the old flat default,
+    // not be capped at it.
+    assert!(auto_size::<[u64; 64]>() > DEFAULT_SIZE);
+}
+
+// A type whose `arbitrary_take_rest` reads differently from its `arbitrary`:
+// the tail `Vec<u8>` consumes everything left in the buffer instead of
+// reading a length prefix first.
+#[derive(Clone, Debug)]
+struct TrailingBytes {
+    tag: u8,
+    rest: Vec<u8>,
+}
+
+impl<'a> Arbitrary<'a> for TrailingBytes {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let tag = u8::arbitrary(u)?;
+        let rest = Vec::<u8>::arbitrary(u)?;
+        Ok(TrailingBytes { tag, rest })
+    }
+
+    fn arbitrary_take_rest(mut u: Unstructured<'a>) -> Result<Self> {
+        let tag = u8::arbitrary(&mut u)?;
+        let rest = u.take_rest().to_vec();
+        Ok(TrailingBytes { tag, rest })
+    }
+}
+
+proptest! {
+    #[test]
+    fn arb_take_rest_consumes_the_whole_buffer(
+        v in proptest_arbitrary_interop::arb_sized_take_rest::<TrailingBytes>(8)
+    ) {
+        // If this were generated via plain `arbitrary` instead of
+        // `arbitrary_take_rest`, `rest` would read its own length from the
+        // buffer instead of soaking up everything left.
+        prop_assert_eq!(v.rest.len(), 7);
+    }
+}
+
+// The entropy-buffer shrinker should still drive a failing case down near
+// the boundary of the failing condition, the same way the old
+// byte-truncation shrinker did -- just without taking up to 256 steps.
+#[test]
+fn shrinker_converges_on_a_minimal_failing_case() {
+    use proptest::test_runner::{TestCaseError, TestError, TestRunner};
+
+    let mut runner = TestRunner::default();
+    let result = runner.run(&arb::<u32>(), |v| {
+        if v > 10 {
+            Err(TestCaseError::fail("too big"))
+        } else {
+            Ok(())
+        }
+    });
+    match result {
+        Err(TestError::Fail(_, v)) => {
+            // An unshrunk failure would almost certainly be some large
+            // random `u32`; the minimizer should land close to the boundary
+            // of `v > 10` rather than leaving it there.
+            assert!(
+                v > 10 && v < 1000,
+                "expected a minimal-ish failing case just above 10, got {v}"
+            );
+        }
+        other => panic!("expected the property to fail, got {other:?}"),
+    }
+}
+
+// `arb_from_bytes` should replay exactly the buffers it's given, in order,
+// rather than drawing from the `TestRunner`'s rng.
+#[test]
+fn arb_from_bytes_replays_the_given_buffers() {
+    use proptest::strategy::{Strategy, ValueTree};
+    use proptest::test_runner::TestRunner;
+    use proptest_arbitrary_interop::arb_from_bytes;
+
+    let bufs = vec![vec![1, 2, 3], vec![4, 5, 6]];
+    let strategy = arb_from_bytes::<Rgb>(bufs.clone());
+    let mut runner = TestRunner::default();
+
+    let seen: Vec<_> = bufs
+        .iter()
+        .map(|_| {
+            let v = strategy.new_tree(&mut runner).unwrap().current();
+            (v.r, v.g, v.b)
+        })
+        .collect();
+    assert_eq!(seen, vec![(1, 2, 3), (4, 5, 6)]);
+}
+
+// `arb_from_corpus` should load every file in the directory as one buffer.
+#[test]
+fn arb_from_corpus_reads_every_file_in_the_directory() {
+    use proptest::strategy::{Strategy, ValueTree};
+    use proptest::test_runner::TestRunner;
+    use proptest_arbitrary_interop::arb_from_corpus;
+
+    let dir = std::env::temp_dir().join(format!(
+        "proptest-arbitrary-interop-test-corpus-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a"), [1, 2, 3]).unwrap();
+    std::fs::write(dir.join("b"), [4, 5, 6]).unwrap();
+
+    let strategy = arb_from_corpus::<Rgb>(&dir).unwrap();
+    let mut runner = TestRunner::default();
+    let mut seen: Vec<_> = (0..2)
+        .map(|_| {
+            let v = strategy.new_tree(&mut runner).unwrap().current();
+            (v.r, v.g, v.b)
+        })
+        .collect();
+    seen.sort();
+
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(seen, vec![(1, 2, 3), (4, 5, 6)]);
+}